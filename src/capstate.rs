@@ -0,0 +1,192 @@
+//! Aggregate POSIX capability state, backed by a single `capget`/`capset` pair.
+//!
+//! Reading or writing the three POSIX sets (effective, permitted, inheritable)
+//! one capability at a time, as the rest of this crate does through `base`,
+//! costs a full syscall round-trip per bit and can race with a concurrent
+//! update of the same thread's state. `CapState` instead reads all three sets
+//! with a single `capget`, lets the caller flip bits in memory, and commits
+//! them back with a single `capset`.
+
+use std::io::Error as IoError;
+
+use libc::{c_int, c_void};
+
+use errors::*;
+use setops::{bitmask, from_bitmask};
+use {Capability, CapSet, CapsHashSet};
+
+const _LINUX_CAPABILITY_VERSION_3: u32 = 0x2008_0522;
+
+#[repr(C)]
+struct CapUserHeader {
+    version: u32,
+    pid: c_int,
+}
+
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+struct CapUserData {
+    effective: u32,
+    permitted: u32,
+    inheritable: u32,
+}
+
+extern "C" {
+    fn capget(hdrp: *mut CapUserHeader, datap: *mut c_void) -> c_int;
+    fn capset(hdrp: *mut CapUserHeader, datap: *const c_void) -> c_int;
+}
+
+/// In-memory snapshot of the three POSIX capability sets of a thread.
+///
+/// `CapState::get` populates all three masks with a single `capget` call;
+/// bits can then be inspected or flipped with `contains`/`raise`/`drop`
+/// before `CapState::set` commits the whole state back with a single
+/// `capset` call.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CapState {
+    /// Effective capabilities, as a bitmask (bit N set means the capability with index N is present).
+    pub effective: u64,
+    /// Permitted capabilities, as a bitmask.
+    pub permitted: u64,
+    /// Inheritable capabilities, as a bitmask.
+    pub inheritable: u64,
+}
+
+impl CapState {
+    /// Read the current POSIX capability sets of thread `tid` with a single `capget`.
+    ///
+    /// If `tid` is `None`, this operates on the current thread (tid=0).
+    pub fn get(tid: Option<i32>) -> Result<Self> {
+        let mut header = CapUserHeader {
+            version: _LINUX_CAPABILITY_VERSION_3,
+            pid: tid.unwrap_or(0),
+        };
+        let mut data = [CapUserData::default(); 2];
+        let ret = unsafe { capget(&mut header, data.as_mut_ptr() as *mut c_void) };
+        if ret != 0 {
+            return Err(IoError::last_os_error().into());
+        }
+        Ok(CapState {
+            effective: pack(data[0].effective, data[1].effective),
+            permitted: pack(data[0].permitted, data[1].permitted),
+            inheritable: pack(data[0].inheritable, data[1].inheritable),
+        })
+    }
+
+    /// Commit this state to the POSIX capability sets of thread `tid` with a single `capset`.
+    ///
+    /// If `tid` is `None`, this operates on the current thread (tid=0).
+    pub fn set(&self, tid: Option<i32>) -> Result<()> {
+        let mut header = CapUserHeader {
+            version: _LINUX_CAPABILITY_VERSION_3,
+            pid: tid.unwrap_or(0),
+        };
+        let data = [
+            CapUserData {
+                effective: low(self.effective),
+                permitted: low(self.permitted),
+                inheritable: low(self.inheritable),
+            },
+            CapUserData {
+                effective: high(self.effective),
+                permitted: high(self.permitted),
+                inheritable: high(self.inheritable),
+            },
+        ];
+        let ret = unsafe { capset(&mut header, data.as_ptr() as *const c_void) };
+        if ret != 0 {
+            return Err(IoError::last_os_error().into());
+        }
+        Ok(())
+    }
+
+    /// Check whether `cap` is present in set `cset` of this state.
+    pub fn contains(&self, cset: CapSet, cap: Capability) -> Result<bool> {
+        Ok(self.mask(cset)? & cap.bitmask() != 0)
+    }
+
+    /// Raise `cap` in set `cset` of this state.
+    pub fn raise(&mut self, cset: CapSet, cap: Capability) -> Result<()> {
+        *self.mask_mut(cset)? |= cap.bitmask();
+        Ok(())
+    }
+
+    /// Drop `cap` from set `cset` of this state.
+    pub fn drop(&mut self, cset: CapSet, cap: Capability) -> Result<()> {
+        *self.mask_mut(cset)? &= !cap.bitmask();
+        Ok(())
+    }
+
+    /// Convert set `cset` of this state into a `CapsHashSet`.
+    pub fn to_set(&self, cset: CapSet) -> Result<CapsHashSet> {
+        Ok(from_bitmask(self.mask(cset)?))
+    }
+
+    /// Replace set `cset` of this state with the capabilities in `set`.
+    pub fn set_from(&mut self, cset: CapSet, set: &CapsHashSet) -> Result<()> {
+        *self.mask_mut(cset)? = bitmask(set);
+        Ok(())
+    }
+
+    fn mask(&self, cset: CapSet) -> Result<u64> {
+        match cset {
+            CapSet::Effective => Ok(self.effective),
+            CapSet::Permitted => Ok(self.permitted),
+            CapSet::Inheritable => Ok(self.inheritable),
+            _ => bail!("operation not supported"),
+        }
+    }
+
+    fn mask_mut(&mut self, cset: CapSet) -> Result<&mut u64> {
+        match cset {
+            CapSet::Effective => Ok(&mut self.effective),
+            CapSet::Permitted => Ok(&mut self.permitted),
+            CapSet::Inheritable => Ok(&mut self.inheritable),
+            _ => bail!("operation not supported"),
+        }
+    }
+}
+
+fn pack(low: u32, high: u32) -> u64 {
+    u64::from(low) | (u64::from(high) << 32)
+}
+
+fn low(v: u64) -> u32 {
+    v as u32
+}
+
+fn high(v: u64) -> u32 {
+    (v >> 32) as u32
+}
+
+#[test]
+fn test_pack_roundtrip() {
+    let v = 0x0000_0007_ffff_ffffu64;
+    assert_eq!(pack(low(v), high(v)), v);
+    assert_eq!(pack(0, 0), 0);
+    assert_eq!(pack(u32::MAX, u32::MAX), u64::MAX);
+}
+
+#[test]
+fn test_raise_drop_contains_to_set() {
+    let mut state = CapState::default();
+    assert!(!state.contains(CapSet::Effective, Capability::CAP_CHOWN).unwrap());
+
+    state.raise(CapSet::Effective, Capability::CAP_CHOWN).unwrap();
+    state.raise(CapSet::Effective, Capability::CAP_SYS_ADMIN).unwrap();
+    assert!(state.contains(CapSet::Effective, Capability::CAP_CHOWN).unwrap());
+
+    let mut set = CapsHashSet::new();
+    set.insert(Capability::CAP_CHOWN);
+    set.insert(Capability::CAP_SYS_ADMIN);
+    assert_eq!(state.to_set(CapSet::Effective).unwrap(), set);
+
+    state.drop(CapSet::Effective, Capability::CAP_CHOWN).unwrap();
+    assert!(!state.contains(CapSet::Effective, Capability::CAP_CHOWN).unwrap());
+    assert!(state.contains(CapSet::Effective, Capability::CAP_SYS_ADMIN).unwrap());
+
+    let mut replacement = CapsHashSet::new();
+    replacement.insert(Capability::CAP_NET_ADMIN);
+    state.set_from(CapSet::Permitted, &replacement).unwrap();
+    assert_eq!(state.to_set(CapSet::Permitted).unwrap(), replacement);
+}
@@ -0,0 +1,208 @@
+//! Support for file capabilities.
+//!
+//! Executables can be granted capabilities directly, without relying on a
+//! setuid bit, by storing them in the `security.capability` extended
+//! attribute. This module reads, writes, and clears that attribute, parsing
+//! the `vfs_cap_data` blob described in `capabilities(7)`.
+
+use std::ffi::CString;
+use std::io::Error as IoError;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+use libc::c_void;
+
+use errors::*;
+use {Capability, CapsHashSet};
+
+const XATTR_NAME_CAPS: &str = "security.capability";
+
+const VFS_CAP_REVISION_MASK: u32 = 0xFF00_0000;
+const VFS_CAP_REVISION_2: u32 = 0x0200_0000;
+const VFS_CAP_REVISION_3: u32 = 0x0300_0000;
+const VFS_CAP_FLAGS_EFFECTIVE: u32 = 0x1;
+
+const REVISION_2_LEN: usize = 20;
+const REVISION_3_LEN: usize = 24;
+
+/// Capabilities stored on an executable file via the `security.capability` xattr.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileCaps {
+    /// Whether the permitted and inheritable sets are raised into the effective set on exec.
+    pub effective: bool,
+    /// File permitted capability set.
+    pub permitted: CapsHashSet,
+    /// File inheritable capability set.
+    pub inheritable: CapsHashSet,
+    /// User namespace root this file's capabilities are scoped to (revision 3 only).
+    pub rootid: Option<u32>,
+}
+
+impl FileCaps {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut permitted = [0u32; 2];
+        let mut inheritable = [0u32; 2];
+        for cap in &self.permitted {
+            let idx = u32::from(cap.index());
+            permitted[(idx / 32) as usize] |= 1u32 << (idx % 32);
+        }
+        for cap in &self.inheritable {
+            let idx = u32::from(cap.index());
+            inheritable[(idx / 32) as usize] |= 1u32 << (idx % 32);
+        }
+
+        let mut magic = if self.rootid.is_some() {
+            VFS_CAP_REVISION_3
+        } else {
+            VFS_CAP_REVISION_2
+        };
+        if self.effective {
+            magic |= VFS_CAP_FLAGS_EFFECTIVE;
+        }
+
+        let mut buf = Vec::with_capacity(REVISION_3_LEN);
+        buf.extend_from_slice(&magic.to_le_bytes());
+        buf.extend_from_slice(&permitted[0].to_le_bytes());
+        buf.extend_from_slice(&inheritable[0].to_le_bytes());
+        buf.extend_from_slice(&permitted[1].to_le_bytes());
+        buf.extend_from_slice(&inheritable[1].to_le_bytes());
+        if let Some(rootid) = self.rootid {
+            buf.extend_from_slice(&rootid.to_le_bytes());
+        }
+        buf
+    }
+
+    fn from_bytes(buf: &[u8]) -> Result<Self> {
+        if buf.len() < REVISION_2_LEN {
+            bail!("truncated vfs_cap_data: {} bytes", buf.len());
+        }
+        let word = |off: usize| u32::from_le_bytes([buf[off], buf[off + 1], buf[off + 2], buf[off + 3]]);
+
+        let magic = word(0);
+        let revision = magic & VFS_CAP_REVISION_MASK;
+        let effective = magic & VFS_CAP_FLAGS_EFFECTIVE != 0;
+        let permitted_words = [word(4), word(12)];
+        let inheritable_words = [word(8), word(16)];
+
+        let rootid = match revision {
+            VFS_CAP_REVISION_2 => None,
+            VFS_CAP_REVISION_3 => {
+                if buf.len() < REVISION_3_LEN {
+                    bail!("truncated vfs_cap_data revision 3: {} bytes", buf.len());
+                }
+                Some(word(20))
+            }
+            _ => bail!("unsupported vfs_cap_data revision: {:#x}", revision),
+        };
+
+        let mut permitted = CapsHashSet::new();
+        let mut inheritable = CapsHashSet::new();
+        for cap in Capability::iter_variants() {
+            let idx = u32::from(cap.index());
+            let word_idx = (idx / 32) as usize;
+            let bit = 1u32 << (idx % 32);
+            if permitted_words[word_idx] & bit != 0 {
+                permitted.insert(cap);
+            }
+            if inheritable_words[word_idx] & bit != 0 {
+                inheritable.insert(cap);
+            }
+        }
+
+        Ok(FileCaps {
+            effective,
+            permitted,
+            inheritable,
+            rootid,
+        })
+    }
+}
+
+#[test]
+fn test_roundtrip_revision2() {
+    let mut permitted = CapsHashSet::new();
+    permitted.insert(Capability::CAP_CHOWN);
+    permitted.insert(Capability::CAP_SYS_ADMIN);
+    let mut inheritable = CapsHashSet::new();
+    inheritable.insert(Capability::CAP_NET_BIND_SERVICE);
+
+    let caps = FileCaps {
+        effective: true,
+        permitted,
+        inheritable,
+        rootid: None,
+    };
+    assert_eq!(FileCaps::from_bytes(&caps.to_bytes()).unwrap(), caps);
+}
+
+#[test]
+fn test_roundtrip_revision3_rootid() {
+    let mut permitted = CapsHashSet::new();
+    permitted.insert(Capability::CAP_CHECKPOINT_RESTORE);
+    let inheritable = CapsHashSet::new();
+
+    let caps = FileCaps {
+        effective: false,
+        permitted,
+        inheritable,
+        rootid: Some(1000),
+    };
+    assert_eq!(FileCaps::from_bytes(&caps.to_bytes()).unwrap(), caps);
+}
+
+fn path_to_cstring(path: &Path) -> Result<CString> {
+    match CString::new(path.as_os_str().as_bytes()) {
+        Ok(c) => Ok(c),
+        Err(_) => bail!("path contains an interior NUL byte"),
+    }
+}
+
+/// Read the file capabilities stored on `path`.
+pub fn read<P: AsRef<Path>>(path: P) -> Result<FileCaps> {
+    let c_path = path_to_cstring(path.as_ref())?;
+    let c_name = CString::new(XATTR_NAME_CAPS).expect("xattr name has no NUL bytes");
+    let mut buf = [0u8; REVISION_3_LEN];
+    let ret = unsafe {
+        libc::getxattr(
+            c_path.as_ptr(),
+            c_name.as_ptr(),
+            buf.as_mut_ptr() as *mut c_void,
+            buf.len(),
+        )
+    };
+    if ret < 0 {
+        return Err(IoError::last_os_error().into());
+    }
+    FileCaps::from_bytes(&buf[..ret as usize])
+}
+
+/// Set the file capabilities of `path` to `caps`.
+pub fn set<P: AsRef<Path>>(path: P, caps: &FileCaps) -> Result<()> {
+    let c_path = path_to_cstring(path.as_ref())?;
+    let c_name = CString::new(XATTR_NAME_CAPS).expect("xattr name has no NUL bytes");
+    let buf = caps.to_bytes();
+    let ret = unsafe {
+        libc::setxattr(
+            c_path.as_ptr(),
+            c_name.as_ptr(),
+            buf.as_ptr() as *const c_void,
+            buf.len(),
+            0,
+        )
+    };
+    if ret != 0 {
+        return Err(IoError::last_os_error().into());
+    }
+    Ok(())
+}
+
+/// Remove any file capabilities stored on `path`.
+pub fn clear<P: AsRef<Path>>(path: P) -> Result<()> {
+    let c_path = path_to_cstring(path.as_ref())?;
+    let c_name = CString::new(XATTR_NAME_CAPS).expect("xattr name has no NUL bytes");
+    let ret = unsafe { libc::removexattr(c_path.as_ptr(), c_name.as_ptr()) };
+    if ret != 0 {
+        return Err(IoError::last_os_error().into());
+    }
+    Ok(())
+}
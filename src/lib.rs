@@ -26,6 +26,8 @@ extern crate error_chain;
 extern crate custom_derive;
 #[macro_use]
 extern crate enum_derive;
+#[cfg(feature = "serde_support")]
+extern crate serde;
 
 use std::iter::FromIterator;
 
@@ -45,6 +47,23 @@ mod ambient;
 // Implementation of Bounding set
 mod bounding;
 
+// Aggregate POSIX capability state backed by a single capget/capset pair
+mod capstate;
+pub use capstate::CapState;
+
+// File capabilities, stored in the security.capability xattr
+pub mod file;
+
+// serde (de)serialization for Capability and CapSet, behind the serde_support feature
+#[cfg(feature = "serde_support")]
+mod serde_impl;
+
+// Per-thread secure-bits flags (prctl PR_GET/SET_SECUREBITS)
+pub mod securebits;
+
+// Set-algebra and subset/privilege-comparison helpers over CapsHashSet
+pub mod setops;
+
 /// Linux capabilities sets.
 ///
 /// All capabilities sets supported by Linux, including standard
@@ -137,51 +156,70 @@ custom_derive! {
         CAP_BLOCK_SUSPEND = nr::CAP_BLOCK_SUSPEND,
         /// CAP_AUDIT_READ (from Linux 3.16).
         CAP_AUDIT_READ = nr::CAP_AUDIT_READ,
+        /// CAP_PERFMON (from Linux 5.8)
+        CAP_PERFMON = nr::CAP_PERFMON,
+        /// CAP_BPF (from Linux 5.8)
+        CAP_BPF = nr::CAP_BPF,
+        /// CAP_CHECKPOINT_RESTORE (from Linux 5.9)
+        CAP_CHECKPOINT_RESTORE = nr::CAP_CHECKPOINT_RESTORE,
     }
 }
 
+/// Single source of truth mapping each `Capability` to its canonical
+/// `CAP_*` name, driving `Display`, `FromStr` and `all()`. Adding a new
+/// kernel capability takes the variant (with its `nr` constant) plus one
+/// entry here.
+const CAPABILITIES: &[(Capability, &str)] = &[
+    (Capability::CAP_CHOWN, "CAP_CHOWN"),
+    (Capability::CAP_DAC_OVERRIDE, "CAP_DAC_OVERRIDE"),
+    (Capability::CAP_DAC_READ_SEARCH, "CAP_DAC_READ_SEARCH"),
+    (Capability::CAP_FOWNER, "CAP_FOWNER"),
+    (Capability::CAP_FSETID, "CAP_FSETID"),
+    (Capability::CAP_KILL, "CAP_KILL"),
+    (Capability::CAP_SETGID, "CAP_SETGID"),
+    (Capability::CAP_SETUID, "CAP_SETUID"),
+    (Capability::CAP_SETPCAP, "CAP_SETPCAP"),
+    (Capability::CAP_LINUX_IMMUTABLE, "CAP_LINUX_IMMUTABLE"),
+    (Capability::CAP_NET_BIND_SERVICE, "CAP_NET_BIND_SERVICE"),
+    (Capability::CAP_NET_BROADCAST, "CAP_NET_BROADCAST"),
+    (Capability::CAP_NET_ADMIN, "CAP_NET_ADMIN"),
+    (Capability::CAP_NET_RAW, "CAP_NET_RAW"),
+    (Capability::CAP_IPC_LOCK, "CAP_IPC_LOCK"),
+    (Capability::CAP_IPC_OWNER, "CAP_IPC_OWNER"),
+    (Capability::CAP_SYS_MODULE, "CAP_SYS_MODULE"),
+    (Capability::CAP_SYS_RAWIO, "CAP_SYS_RAWIO"),
+    (Capability::CAP_SYS_CHROOT, "CAP_SYS_CHROOT"),
+    (Capability::CAP_SYS_PTRACE, "CAP_SYS_PTRACE"),
+    (Capability::CAP_SYS_PACCT, "CAP_SYS_PACCT"),
+    (Capability::CAP_SYS_ADMIN, "CAP_SYS_ADMIN"),
+    (Capability::CAP_SYS_BOOT, "CAP_SYS_BOOT"),
+    (Capability::CAP_SYS_NICE, "CAP_SYS_NICE"),
+    (Capability::CAP_SYS_RESOURCE, "CAP_SYS_RESOURCE"),
+    (Capability::CAP_SYS_TIME, "CAP_SYS_TIME"),
+    (Capability::CAP_SYS_TTY_CONFIG, "CAP_SYS_TTY_CONFIG"),
+    (Capability::CAP_MKNOD, "CAP_MKNOD"),
+    (Capability::CAP_LEASE, "CAP_LEASE"),
+    (Capability::CAP_AUDIT_WRITE, "CAP_AUDIT_WRITE"),
+    (Capability::CAP_AUDIT_CONTROL, "CAP_AUDIT_CONTROL"),
+    (Capability::CAP_SETFCAP, "CAP_SETFCAP"),
+    (Capability::CAP_MAC_OVERRIDE, "CAP_MAC_OVERRIDE"),
+    (Capability::CAP_MAC_ADMIN, "CAP_MAC_ADMIN"),
+    (Capability::CAP_SYSLOG, "CAP_SYSLOG"),
+    (Capability::CAP_WAKE_ALARM, "CAP_WAKE_ALARM"),
+    (Capability::CAP_BLOCK_SUSPEND, "CAP_BLOCK_SUSPEND"),
+    (Capability::CAP_AUDIT_READ, "CAP_AUDIT_READ"),
+    (Capability::CAP_PERFMON, "CAP_PERFMON"),
+    (Capability::CAP_BPF, "CAP_BPF"),
+    (Capability::CAP_CHECKPOINT_RESTORE, "CAP_CHECKPOINT_RESTORE"),
+];
+
 impl std::fmt::Display for Capability {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let name = match self {
-            &Capability::CAP_CHOWN => "CAP_CHOWN",
-            &Capability::CAP_DAC_OVERRIDE => "CAP_DAC_OVERRIDE",
-            &Capability::CAP_DAC_READ_SEARCH => "CAP_DAC_READ_SEARCH",
-            &Capability::CAP_FOWNER => "CAP_FOWNER",
-            &Capability::CAP_FSETID => "CAP_FSETID",
-            &Capability::CAP_KILL => "CAP_KILL",
-            &Capability::CAP_SETGID => "CAP_SETGID",
-            &Capability::CAP_SETUID => "CAP_SETUID",
-            &Capability::CAP_SETPCAP => "CAP_SETPCAP",
-            &Capability::CAP_LINUX_IMMUTABLE => "CAP_LINUX_IMMUTABLE",
-            &Capability::CAP_NET_BIND_SERVICE => "CAP_NET_BIND_SERVICE",
-            &Capability::CAP_NET_BROADCAST => "CAP_NET_BROADCAST",
-            &Capability::CAP_NET_ADMIN => "CAP_NET_ADMIN",
-            &Capability::CAP_NET_RAW => "CAP_NET_RAW",
-            &Capability::CAP_IPC_LOCK => "CAP_IPC_LOCK",
-            &Capability::CAP_IPC_OWNER => "CAP_IPC_OWNER",
-            &Capability::CAP_SYS_MODULE => "CAP_SYS_MODULE",
-            &Capability::CAP_SYS_RAWIO => "CAP_SYS_RAWIO",
-            &Capability::CAP_SYS_CHROOT => "CAP_SYS_CHROOT",
-            &Capability::CAP_SYS_PTRACE => "CAP_SYS_PTRACE",
-            &Capability::CAP_SYS_PACCT => "CAP_SYS_PACCT",
-            &Capability::CAP_SYS_ADMIN => "CAP_SYS_ADMIN",
-            &Capability::CAP_SYS_BOOT => "CAP_SYS_BOOT",
-            &Capability::CAP_SYS_NICE => "CAP_SYS_NICE",
-            &Capability::CAP_SYS_RESOURCE => "CAP_SYS_RESOURCE",
-            &Capability::CAP_SYS_TIME => "CAP_SYS_TIME",
-            &Capability::CAP_SYS_TTY_CONFIG => "CAP_SYS_TTY_CONFIG",
-            &Capability::CAP_MKNOD => "CAP_MKNOD",
-            &Capability::CAP_LEASE => "CAP_LEASE",
-            &Capability::CAP_AUDIT_WRITE => "CAP_AUDIT_WRITE",
-            &Capability::CAP_AUDIT_CONTROL => "CAP_AUDIT_CONTROL",
-            &Capability::CAP_SETFCAP => "CAP_SETFCAP",
-            &Capability::CAP_MAC_OVERRIDE => "CAP_MAC_OVERRIDE",
-            &Capability::CAP_MAC_ADMIN => "CAP_MAC_ADMIN",
-            &Capability::CAP_SYSLOG => "CAP_SYSLOG",
-            &Capability::CAP_WAKE_ALARM => "CAP_WAKE_ALARM",
-            &Capability::CAP_BLOCK_SUSPEND => "CAP_BLOCK_SUSPEND",
-            &Capability::CAP_AUDIT_READ => "CAP_AUDIT_READ",
-        };
+        let name = CAPABILITIES
+            .iter()
+            .find(|(cap, _)| cap == self)
+            .map(|(_, name)| *name)
+            .expect("every Capability variant has an entry in CAPABILITIES");
         write!(f, "{}", name)
     }
 }
@@ -190,47 +228,11 @@ impl std::str::FromStr for Capability {
     type Err = errors::Error;
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        match s {
-            "CAP_CHOWN" => Ok(Capability::CAP_CHOWN),
-            "CAP_DAC_OVERRIDE" => Ok(Capability::CAP_DAC_OVERRIDE),
-            "CAP_DAC_READ_SEARCH" => Ok(Capability::CAP_DAC_READ_SEARCH),
-            "CAP_FOWNER" => Ok(Capability::CAP_FOWNER),
-            "CAP_FSETID" => Ok(Capability::CAP_FSETID),
-            "CAP_KILL" => Ok(Capability::CAP_KILL),
-            "CAP_SETGID" => Ok(Capability::CAP_SETGID),
-            "CAP_SETUID" => Ok(Capability::CAP_SETUID),
-            "CAP_SETPCAP" => Ok(Capability::CAP_SETPCAP),
-            "CAP_LINUX_IMMUTABLE" => Ok(Capability::CAP_LINUX_IMMUTABLE),
-            "CAP_NET_BIND_SERVICE" => Ok(Capability::CAP_NET_BIND_SERVICE),
-            "CAP_NET_BROADCAST" => Ok(Capability::CAP_NET_BROADCAST),
-            "CAP_NET_ADMIN" => Ok(Capability::CAP_NET_ADMIN),
-            "CAP_NET_RAW" => Ok(Capability::CAP_NET_RAW),
-            "CAP_IPC_LOCK" => Ok(Capability::CAP_IPC_LOCK),
-            "CAP_IPC_OWNER" => Ok(Capability::CAP_IPC_OWNER),
-            "CAP_SYS_MODULE" => Ok(Capability::CAP_SYS_MODULE),
-            "CAP_SYS_RAWIO" => Ok(Capability::CAP_SYS_RAWIO),
-            "CAP_SYS_CHROOT" => Ok(Capability::CAP_SYS_CHROOT),
-            "CAP_SYS_PTRACE" => Ok(Capability::CAP_SYS_PTRACE),
-            "CAP_SYS_PACCT" => Ok(Capability::CAP_SYS_PACCT),
-            "CAP_SYS_ADMIN" => Ok(Capability::CAP_SYS_ADMIN),
-            "CAP_SYS_BOOT" => Ok(Capability::CAP_SYS_BOOT),
-            "CAP_SYS_NICE" => Ok(Capability::CAP_SYS_NICE),
-            "CAP_SYS_RESOURCE" => Ok(Capability::CAP_SYS_RESOURCE),
-            "CAP_SYS_TIME" => Ok(Capability::CAP_SYS_TIME),
-            "CAP_SYS_TTY_CONFIG" => Ok(Capability::CAP_SYS_TTY_CONFIG),
-            "CAP_MKNOD" => Ok(Capability::CAP_MKNOD),
-            "CAP_LEASE" => Ok(Capability::CAP_LEASE),
-            "CAP_AUDIT_WRITE" => Ok(Capability::CAP_AUDIT_WRITE),
-            "CAP_AUDIT_CONTROL" => Ok(Capability::CAP_AUDIT_CONTROL),
-            "CAP_SETFCAP" => Ok(Capability::CAP_SETFCAP),
-            "CAP_MAC_OVERRIDE" => Ok(Capability::CAP_MAC_OVERRIDE),
-            "CAP_MAC_ADMIN" => Ok(Capability::CAP_MAC_ADMIN),
-            "CAP_SYSLOG" => Ok(Capability::CAP_SYSLOG),
-            "CAP_WAKE_ALARM" => Ok(Capability::CAP_WAKE_ALARM),
-            "CAP_BLOCK_SUSPEND" => Ok(Capability::CAP_BLOCK_SUSPEND),
-            "CAP_AUDIT_READ" => Ok(Capability::CAP_AUDIT_READ),
-            _ => Err(ErrorKind::InvalidCapName(s.to_string()).into()),
-        }
+        CAPABILITIES
+            .iter()
+            .find(|(_, name)| *name == s)
+            .map(|(cap, _)| *cap)
+            .ok_or_else(|| ErrorKind::InvalidCapName(s.to_string()).into())
     }
 }
 
@@ -342,47 +344,7 @@ pub fn drop(tid: Option<i32>, cset: CapSet, cap: Capability) -> Result<()> {
 
 /// Return an `HashSet` with all known capabilities.
 pub fn all() -> CapsHashSet {
-    let slice = vec![
-        Capability::CAP_CHOWN,
-        Capability::CAP_DAC_OVERRIDE,
-        Capability::CAP_DAC_READ_SEARCH,
-        Capability::CAP_FOWNER,
-        Capability::CAP_FSETID,
-        Capability::CAP_KILL,
-        Capability::CAP_SETGID,
-        Capability::CAP_SETUID,
-        Capability::CAP_SETPCAP,
-        Capability::CAP_LINUX_IMMUTABLE,
-        Capability::CAP_NET_BIND_SERVICE,
-        Capability::CAP_NET_BROADCAST,
-        Capability::CAP_NET_ADMIN,
-        Capability::CAP_NET_RAW,
-        Capability::CAP_IPC_LOCK,
-        Capability::CAP_IPC_OWNER,
-        Capability::CAP_SYS_MODULE,
-        Capability::CAP_SYS_RAWIO,
-        Capability::CAP_SYS_CHROOT,
-        Capability::CAP_SYS_PTRACE,
-        Capability::CAP_SYS_PACCT,
-        Capability::CAP_SYS_ADMIN,
-        Capability::CAP_SYS_BOOT,
-        Capability::CAP_SYS_NICE,
-        Capability::CAP_SYS_RESOURCE,
-        Capability::CAP_SYS_TIME,
-        Capability::CAP_SYS_TTY_CONFIG,
-        Capability::CAP_MKNOD,
-        Capability::CAP_LEASE,
-        Capability::CAP_AUDIT_WRITE,
-        Capability::CAP_AUDIT_CONTROL,
-        Capability::CAP_SETFCAP,
-        Capability::CAP_MAC_OVERRIDE,
-        Capability::CAP_MAC_ADMIN,
-        Capability::CAP_SYSLOG,
-        Capability::CAP_WAKE_ALARM,
-        Capability::CAP_BLOCK_SUSPEND,
-        Capability::CAP_AUDIT_READ,
-    ];
-    CapsHashSet::from_iter(slice)
+    CapsHashSet::from_iter(CAPABILITIES.iter().map(|(cap, _)| *cap))
 }
 
 #[test]
@@ -404,3 +366,8 @@ fn test_parse_invalid() {
     let p2: Result<Capability> = "CAP_BAR".parse();
     assert!(p2.is_err());
 }
+
+#[test]
+fn test_checkpoint_restore_bitmask() {
+    assert_eq!(Capability::CAP_CHECKPOINT_RESTORE.bitmask(), 1u64 << 40);
+}
@@ -0,0 +1,144 @@
+//! Per-thread secure-bits, read and written via `prctl(2)`'s
+//! `PR_GET_SECUREBITS`/`PR_SET_SECUREBITS`.
+//!
+//! Unlike the POSIX/ambient/bounding sets handled elsewhere in this crate,
+//! these bits don't hold capabilities themselves but control how uid 0
+//! acquires and keeps them across `setuid`/`execve`.
+
+use std::io::Error as IoError;
+
+use errors::*;
+
+const PR_GET_SECUREBITS: libc::c_int = 27;
+const PR_SET_SECUREBITS: libc::c_int = 28;
+
+/// Secure-bits flags, one bit per `prctl(2)` `SECBIT_*` constant.
+///
+/// Each toggle has a matching `_LOCKED` bit that makes it irreversible once
+/// set; `set` always preserves whatever lock bits are already active,
+/// regardless of what is passed in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SecureBits(u8);
+
+impl SecureBits {
+    /// SECBIT_NOROOT: uid 0 does not get capabilities back across `execve`.
+    pub const NOROOT: SecureBits = SecureBits(1 << 0);
+    /// SECBIT_NOROOT_LOCKED: `NOROOT` can no longer be changed.
+    pub const NOROOT_LOCKED: SecureBits = SecureBits(1 << 1);
+    /// SECBIT_NO_SETUID_FIXUP: uid transitions do not adjust capability sets.
+    pub const NO_SETUID_FIXUP: SecureBits = SecureBits(1 << 2);
+    /// SECBIT_NO_SETUID_FIXUP_LOCKED: `NO_SETUID_FIXUP` can no longer be changed.
+    pub const NO_SETUID_FIXUP_LOCKED: SecureBits = SecureBits(1 << 3);
+    /// SECBIT_KEEP_CAPS: permitted capabilities survive a uid transition away from 0.
+    pub const KEEP_CAPS: SecureBits = SecureBits(1 << 4);
+    /// SECBIT_KEEP_CAPS_LOCKED: `KEEP_CAPS` can no longer be changed.
+    pub const KEEP_CAPS_LOCKED: SecureBits = SecureBits(1 << 5);
+    /// SECBIT_NO_CAP_AMBIENT_RAISE: capabilities cannot be raised into the ambient set.
+    pub const NO_CAP_AMBIENT_RAISE: SecureBits = SecureBits(1 << 6);
+    /// SECBIT_NO_CAP_AMBIENT_RAISE_LOCKED: `NO_CAP_AMBIENT_RAISE` can no longer be changed.
+    pub const NO_CAP_AMBIENT_RAISE_LOCKED: SecureBits = SecureBits(1 << 7);
+
+    const LOCK_MASK: u8 = Self::NOROOT_LOCKED.0
+        | Self::NO_SETUID_FIXUP_LOCKED.0
+        | Self::KEEP_CAPS_LOCKED.0
+        | Self::NO_CAP_AMBIENT_RAISE_LOCKED.0;
+
+    /// The empty set of secure-bits.
+    pub fn empty() -> Self {
+        SecureBits(0)
+    }
+
+    /// Build a `SecureBits` from a raw `prctl` bitmask, ignoring unknown bits.
+    pub fn from_bits_truncate(bits: u8) -> Self {
+        SecureBits(bits)
+    }
+
+    /// The raw `prctl` bitmask for this value.
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Whether every bit set in `other` is also set in `self`.
+    pub fn contains(self, other: SecureBits) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for SecureBits {
+    type Output = SecureBits;
+    fn bitor(self, rhs: SecureBits) -> SecureBits {
+        SecureBits(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for SecureBits {
+    fn bitor_assign(&mut self, rhs: SecureBits) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl std::ops::BitAnd for SecureBits {
+    type Output = SecureBits;
+    fn bitand(self, rhs: SecureBits) -> SecureBits {
+        SecureBits(self.0 & rhs.0)
+    }
+}
+
+/// Read the current secure-bits of the calling thread.
+pub fn get() -> Result<SecureBits> {
+    let ret = unsafe { libc::prctl(PR_GET_SECUREBITS, 0, 0, 0, 0) };
+    if ret < 0 {
+        return Err(IoError::last_os_error().into());
+    }
+    Ok(SecureBits::from_bits_truncate(ret as u8))
+}
+
+/// Set the secure-bits of the calling thread to `bits`.
+///
+/// Any `_LOCKED` bit already set on the thread is preserved even if it is
+/// absent from `bits`, since the kernel makes those bits irreversible
+/// anyway. Returns an error (typically `EPERM`) if the caller lacks
+/// `CAP_SETPCAP` or tries to flip a bit whose lock is already held.
+pub fn set(bits: SecureBits) -> Result<()> {
+    let current = get()?;
+    let preserved = SecureBits(bits.0 | (current.0 & SecureBits::LOCK_MASK));
+    let ret = unsafe { libc::prctl(PR_SET_SECUREBITS, libc::c_ulong::from(preserved.0), 0, 0, 0) };
+    if ret < 0 {
+        return Err(IoError::last_os_error().into());
+    }
+    Ok(())
+}
+
+/// Raise `flag` in the calling thread's secure-bits, keeping the rest unchanged.
+pub fn raise(flag: SecureBits) -> Result<()> {
+    let current = get()?;
+    set(current | flag)
+}
+
+/// Clear `flag` from the calling thread's secure-bits, keeping the rest unchanged.
+///
+/// As with `set`, this has no effect (and surfaces `EPERM`) if `flag` is
+/// already locked.
+pub fn drop(flag: SecureBits) -> Result<()> {
+    let current = get()?;
+    set(SecureBits(current.0 & !flag.0))
+}
+
+#[test]
+fn test_bitor_contains() {
+    let bits = SecureBits::NOROOT | SecureBits::KEEP_CAPS;
+    assert!(bits.contains(SecureBits::NOROOT));
+    assert!(bits.contains(SecureBits::KEEP_CAPS));
+    assert!(!bits.contains(SecureBits::NO_SETUID_FIXUP));
+}
+
+#[test]
+fn test_lock_bits_survive_or() {
+    let locked = SecureBits::NOROOT_LOCKED | SecureBits::KEEP_CAPS_LOCKED;
+    assert_eq!(locked.bits() & SecureBits::LOCK_MASK, locked.bits());
+
+    let combined = locked | SecureBits::NOROOT;
+    assert!(combined.contains(SecureBits::NOROOT_LOCKED));
+    assert!(combined.contains(SecureBits::KEEP_CAPS_LOCKED));
+    assert!(combined.contains(SecureBits::NOROOT));
+}
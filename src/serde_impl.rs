@@ -0,0 +1,100 @@
+//! `serde` support for `Capability` and `CapSet`, gated behind the
+//! `serde_support` feature so the default build stays free of extra
+//! dependencies.
+//!
+//! `Capability` serializes as its canonical `CAP_*` name, reusing the
+//! `Display`/`FromStr` round-trip so an unknown name deserializes to the
+//! same `InvalidCapName` error as `str::parse`. `CapSet` serializes as a
+//! lowercase tag.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+use {CapSet, Capability};
+
+impl Serialize for Capability {
+    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Capability {
+    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct CapabilityVisitor;
+
+        impl<'de> Visitor<'de> for CapabilityVisitor {
+            type Value = Capability;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a CAP_* capability name")
+            }
+
+            fn visit_str<E>(self, v: &str) -> ::std::result::Result<Capability, E>
+            where
+                E: de::Error,
+            {
+                Capability::from_str(v).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(CapabilityVisitor)
+    }
+}
+
+impl Serialize for CapSet {
+    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let tag = match *self {
+            CapSet::Ambient => "ambient",
+            CapSet::Bounding => "bounding",
+            CapSet::Effective => "effective",
+            CapSet::Inheritable => "inheritable",
+            CapSet::Permitted => "permitted",
+        };
+        serializer.serialize_str(tag)
+    }
+}
+
+impl<'de> Deserialize<'de> for CapSet {
+    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct CapSetVisitor;
+
+        impl<'de> Visitor<'de> for CapSetVisitor {
+            type Value = CapSet;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("one of: ambient, bounding, effective, inheritable, permitted")
+            }
+
+            fn visit_str<E>(self, v: &str) -> ::std::result::Result<CapSet, E>
+            where
+                E: de::Error,
+            {
+                match v {
+                    "ambient" => Ok(CapSet::Ambient),
+                    "bounding" => Ok(CapSet::Bounding),
+                    "effective" => Ok(CapSet::Effective),
+                    "inheritable" => Ok(CapSet::Inheritable),
+                    "permitted" => Ok(CapSet::Permitted),
+                    _ => Err(E::custom(format!("invalid capability set: {}", v))),
+                }
+            }
+        }
+
+        deserializer.deserialize_str(CapSetVisitor)
+    }
+}
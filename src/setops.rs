@@ -0,0 +1,79 @@
+//! Set-algebra and comparison helpers over `CapsHashSet`.
+//!
+//! `CapsHashSet` is a plain `HashSet`, so most of this just forwards to the
+//! standard library; `bitmask`/`from_bitmask` additionally give a compact
+//! `u64` representation for code that would rather compare masks than
+//! iterate sets, and `diff` reads two live sets of a thread in one call.
+
+use {CapSet, Capability, CapsHashSet};
+use errors::*;
+
+/// Pack `set` into a `u64` bitmask, one bit per capability index.
+pub fn bitmask(set: &CapsHashSet) -> u64 {
+    set.iter().fold(0u64, |acc, cap| acc | cap.bitmask())
+}
+
+/// Unpack a `u64` bitmask, as produced by `bitmask`, back into a `CapsHashSet`.
+pub fn from_bitmask(mask: u64) -> CapsHashSet {
+    Capability::iter_variants().filter(|cap| mask & cap.bitmask() != 0).collect()
+}
+
+/// Capabilities present in either `a` or `b`.
+pub fn union(a: &CapsHashSet, b: &CapsHashSet) -> CapsHashSet {
+    a.union(b).cloned().collect()
+}
+
+/// Capabilities present in both `a` and `b`.
+pub fn intersection(a: &CapsHashSet, b: &CapsHashSet) -> CapsHashSet {
+    a.intersection(b).cloned().collect()
+}
+
+/// Capabilities present in `a` but not in `b`.
+pub fn difference(a: &CapsHashSet, b: &CapsHashSet) -> CapsHashSet {
+    a.difference(b).cloned().collect()
+}
+
+/// Whether every capability in `a` is also in `b`.
+pub fn is_subset(a: &CapsHashSet, b: &CapsHashSet) -> bool {
+    a.is_subset(b)
+}
+
+/// Whether every capability in `b` is also in `a`.
+pub fn is_superset(a: &CapsHashSet, b: &CapsHashSet) -> bool {
+    a.is_superset(b)
+}
+
+/// Capabilities that differ between `cset_a` and `cset_b` of thread `tid`.
+///
+/// Useful for auditing what an `exec` or a `drop` actually changed, without
+/// having to read both sets and diff them by hand.
+pub fn diff(tid: Option<i32>, cset_a: CapSet, cset_b: CapSet) -> Result<CapsHashSet> {
+    let a = ::read(tid, cset_a)?;
+    let b = ::read(tid, cset_b)?;
+    Ok(a.symmetric_difference(&b).cloned().collect())
+}
+
+#[test]
+fn test_bitmask_roundtrip() {
+    let mut set = CapsHashSet::new();
+    set.insert(Capability::CAP_CHOWN);
+    set.insert(Capability::CAP_SYS_ADMIN);
+    let mask = bitmask(&set);
+    assert_eq!(from_bitmask(mask), set);
+}
+
+#[test]
+fn test_subset_superset() {
+    let mut small = CapsHashSet::new();
+    small.insert(Capability::CAP_CHOWN);
+
+    let mut big = CapsHashSet::new();
+    big.insert(Capability::CAP_CHOWN);
+    big.insert(Capability::CAP_SYS_ADMIN);
+
+    assert!(is_subset(&small, &big));
+    assert!(is_superset(&big, &small));
+    assert!(!is_subset(&big, &small));
+    assert_eq!(intersection(&small, &big), small);
+    assert_eq!(difference(&big, &small).len(), 1);
+}